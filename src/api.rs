@@ -0,0 +1,498 @@
+//! The HTTP surface: four REST endpoints that translate directly onto the
+//! `routine` entry points, wrapping every reply in a uniform `{result, error}`
+//! carrier, plus a JSON-RPC 2.0 endpoint (`/rpc`) that dispatches onto the same
+//! entry points and replies with JSON-RPC response objects.
+//!
+//! Bodies and replies are content-negotiated: a client that sends
+//! `Accept: application/msgpack` (or posts with that `Content-Type`) gets
+//! MessagePack through `rmp-serde`; everyone else keeps the JSON they always
+//! had. The MessagePack path is gated behind the `msgpack` crate feature so
+//! the extra serde backend stays optional.
+
+use std::io::{Cursor, Read};
+
+use rocket::{Config, Data, Request, Rocket, State};
+use rocket::data::{self, FromData};
+use rocket::response::{self, Responder, Response};
+use rocket::http::{ContentType, Status};
+use rocket::Outcome;
+use serde::{Deserialize, Serialize};
+use serde::ser::{Serializer, SerializeMap};
+use serde::de::{Deserializer, DeserializeOwned};
+use serde_json::{self, Value};
+
+use routine::{self, Budget, EvalError, Input};
+
+/// The uniform reply envelope. Exactly one of `result`/`error` is populated.
+#[derive(Serialize)]
+struct Carrier {
+    result: Option<Value>,
+    error: Option<ApiError>,
+}
+
+impl Carrier {
+    fn ok<T: Serialize>(value: T) -> Carrier {
+        Carrier {
+            result: Some(serde_json::to_value(value).unwrap()),
+            error: None,
+        }
+    }
+
+    fn err(error: ApiError) -> Carrier {
+        Carrier {
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// A machine-readable failure class. Every error leaving the API is assigned
+/// one of these before it is rendered, so clients can match on `kind` (a
+/// stable string code) instead of sniffing a free-form message.
+#[derive(Clone, Debug)]
+pub enum ApiError {
+    /// No routine is registered under the requested name.
+    UnknownRoutine { routine: String },
+    /// The input did not satisfy the routine's type signature.
+    TypeMismatch { routine: String, message: String },
+    /// The request body could not be parsed into an `Input`.
+    InvalidInput { message: String },
+    /// The routine was found and well-typed but evaluation failed.
+    EvalFailure { routine: String, message: String },
+    /// Evaluation ran past its wall-clock budget.
+    Timeout { routine: String, message: String },
+    /// Evaluation tried to touch more steps than its budget allows.
+    StepLimitExceeded { routine: String, message: String },
+    /// An unexpected server-side failure.
+    Internal { message: String },
+}
+
+impl ApiError {
+    /// The stable string code for this variant.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            ApiError::UnknownRoutine { .. } => "unknown_routine",
+            ApiError::TypeMismatch { .. } => "type_mismatch",
+            ApiError::InvalidInput { .. } => "invalid_input",
+            ApiError::EvalFailure { .. } => "eval_failure",
+            ApiError::Timeout { .. } => "timeout",
+            ApiError::StepLimitExceeded { .. } => "step_limit_exceeded",
+            ApiError::Internal { .. } => "internal",
+        }
+    }
+
+    /// The stable numeric code for this variant, used where a transport (e.g.
+    /// JSON-RPC) wants an integer rather than a string.
+    pub fn code(&self) -> i64 {
+        match *self {
+            ApiError::UnknownRoutine { .. } => 404,
+            ApiError::TypeMismatch { .. } => 422,
+            ApiError::InvalidInput { .. } => 400,
+            ApiError::EvalFailure { .. } => 500,
+            ApiError::Timeout { .. } => 504,
+            ApiError::StepLimitExceeded { .. } => 429,
+            ApiError::Internal { .. } => 500,
+        }
+    }
+
+    /// The human-readable message for this error.
+    pub fn message(&self) -> String {
+        match *self {
+            ApiError::UnknownRoutine { ref routine } => format!("unknown routine `{}`", routine),
+            ApiError::TypeMismatch { ref message, .. }
+            | ApiError::EvalFailure { ref message, .. }
+            | ApiError::Timeout { ref message, .. }
+            | ApiError::StepLimitExceeded { ref message, .. }
+            | ApiError::InvalidInput { ref message }
+            | ApiError::Internal { ref message } => message.clone(),
+        }
+    }
+
+    /// The routine the error is about, when one applies.
+    pub fn routine(&self) -> Option<&str> {
+        match *self {
+            ApiError::UnknownRoutine { ref routine }
+            | ApiError::TypeMismatch { ref routine, .. }
+            | ApiError::EvalFailure { ref routine, .. }
+            | ApiError::Timeout { ref routine, .. }
+            | ApiError::StepLimitExceeded { ref routine, .. } => Some(routine),
+            ApiError::InvalidInput { .. } | ApiError::Internal { .. } => None,
+        }
+    }
+
+    /// Lift a routine-layer `EvalError` into the API taxonomy, attaching the
+    /// routine name the endpoint was called with.
+    fn from_eval(routine: &str, error: EvalError) -> ApiError {
+        let message = error.to_string();
+        match error {
+            EvalError::UnknownRoutine(name) => ApiError::UnknownRoutine { routine: name },
+            EvalError::TypeMismatch(_) => ApiError::TypeMismatch {
+                routine: routine.to_string(),
+                message,
+            },
+            EvalError::Undefined(_) => ApiError::EvalFailure {
+                routine: routine.to_string(),
+                message,
+            },
+            EvalError::Timeout(_) => ApiError::Timeout {
+                routine: routine.to_string(),
+                message,
+            },
+            EvalError::StepLimitExceeded(_) => ApiError::StepLimitExceeded {
+                routine: routine.to_string(),
+                message,
+            },
+        }
+    }
+}
+
+impl Serialize for ApiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // A concrete length is required by non-self-describing formats such as
+        // MessagePack, which is the whole point of the error-over-msgpack path.
+        let len = if self.routine().is_some() { 3 } else { 2 };
+        let mut map = serializer.serialize_map(Some(len))?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.message())?;
+        if let Some(routine) = self.routine() {
+            map.serialize_entry("routine", routine)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ApiError, D::Error> {
+        #[derive(Deserialize)]
+        struct Raw {
+            kind: String,
+            #[serde(default)]
+            message: String,
+            #[serde(default)]
+            routine: Option<String>,
+        }
+        let raw = Raw::deserialize(deserializer)?;
+        let routine = raw.routine.unwrap_or_default();
+        Ok(match raw.kind.as_str() {
+            "unknown_routine" => ApiError::UnknownRoutine { routine },
+            "type_mismatch" => ApiError::TypeMismatch {
+                routine,
+                message: raw.message,
+            },
+            "invalid_input" => ApiError::InvalidInput {
+                message: raw.message,
+            },
+            "eval_failure" => ApiError::EvalFailure {
+                routine,
+                message: raw.message,
+            },
+            "timeout" => ApiError::Timeout {
+                routine,
+                message: raw.message,
+            },
+            "step_limit_exceeded" => ApiError::StepLimitExceeded {
+                routine,
+                message: raw.message,
+            },
+            _ => ApiError::Internal {
+                message: raw.message,
+            },
+        })
+    }
+}
+
+/// `true` when the request's `Accept` header opts into MessagePack.
+fn wants_msgpack(req: &Request) -> bool {
+    req.headers()
+        .get("Accept")
+        .any(|a| a.contains("application/msgpack"))
+}
+
+/// `true` when the posted body is MessagePack rather than JSON.
+fn body_is_msgpack(req: &Request) -> bool {
+    req.content_type()
+        .map(|ct| ct.top() == "application" && ct.sub() == "msgpack")
+        .unwrap_or(false)
+}
+
+/// A reply that renders as MessagePack or JSON depending on the request's
+/// `Accept` header.
+struct Negotiated(Carrier);
+
+impl<'r> Responder<'r> for Negotiated {
+    fn respond_to(self, req: &Request) -> response::Result<'r> {
+        let (content_type, body) = render(&self.0, wants_msgpack(req));
+        Response::build()
+            .header(content_type)
+            .sized_body(Cursor::new(body))
+            .ok()
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn render<T: Serialize>(value: &T, msgpack: bool) -> (ContentType, Vec<u8>) {
+    if msgpack {
+        let bytes = ::rmp_serde::to_vec_named(value).unwrap();
+        (ContentType::new("application", "msgpack"), bytes)
+    } else {
+        (ContentType::JSON, serde_json::to_vec(value).unwrap())
+    }
+}
+
+#[cfg(not(feature = "msgpack"))]
+fn render<T: Serialize>(value: &T, _msgpack: bool) -> (ContentType, Vec<u8>) {
+    (ContentType::JSON, serde_json::to_vec(value).unwrap())
+}
+
+/// A request body decoded from either JSON or MessagePack.
+///
+/// Decoding is deferred into a `Result` the handler unwraps rather than failing
+/// the guard outright: a malformed body must surface as a typed `InvalidInput`
+/// carrier (or a JSON-RPC parse error), not a bare Rocket 400 that the client
+/// cannot deserialize into the `{result, error}` envelope.
+struct Body<T>(Result<T, String>)
+where
+    T: DeserializeOwned;
+
+impl<T: DeserializeOwned> FromData for Body<T> {
+    type Error = String;
+
+    fn from_data(req: &Request, data: Data) -> data::Outcome<Self, String> {
+        let mut bytes = Vec::new();
+        if let Err(e) = data.open().read_to_end(&mut bytes) {
+            return Outcome::Success(Body(Err(format!("could not read body: {}", e))));
+        }
+        Outcome::Success(Body(decode(&bytes, body_is_msgpack(req))))
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn decode<T: DeserializeOwned>(bytes: &[u8], msgpack: bool) -> Result<T, String> {
+    if msgpack {
+        ::rmp_serde::from_slice(bytes).map_err(|e| format!("invalid msgpack body: {}", e))
+    } else {
+        serde_json::from_slice(bytes).map_err(|e| format!("invalid json body: {}", e))
+    }
+}
+
+#[cfg(not(feature = "msgpack"))]
+fn decode<T: DeserializeOwned>(bytes: &[u8], _msgpack: bool) -> Result<T, String> {
+    serde_json::from_slice(bytes).map_err(|e| format!("invalid json body: {}", e))
+}
+
+#[derive(FromForm)]
+struct CountParam {
+    count: Option<u32>,
+}
+
+#[derive(FromForm)]
+struct BudgetParam {
+    timeout_ms: Option<u64>,
+    max_steps: Option<u64>,
+}
+
+#[get("/find?<params>")]
+fn find(params: CountParam) -> Negotiated {
+    Negotiated(Carrier::ok(routine::find(params.count.unwrap_or(100))))
+}
+
+#[get("/examples/<name>")]
+fn examples(name: String) -> Negotiated {
+    if !routine::exists(&name) {
+        return Negotiated(Carrier::err(ApiError::UnknownRoutine { routine: name }));
+    }
+    Negotiated(Carrier::ok(routine::examples(&name)))
+}
+
+#[post("/eval/<name>?<params>", data = "<body>")]
+fn eval(
+    name: String,
+    params: BudgetParam,
+    body: Body<Input>,
+    default_budget: State<Budget>,
+) -> Negotiated {
+    let input = match body.0 {
+        Ok(input) => input,
+        Err(message) => return Negotiated(Carrier::err(ApiError::InvalidInput { message })),
+    };
+    let budget = default_budget.with_overrides(params.timeout_ms, params.max_steps);
+    match routine::eval_with(&name, &input, budget) {
+        Ok(output) => Negotiated(Carrier::ok(output)),
+        Err(e) => Negotiated(Carrier::err(ApiError::from_eval(&name, e))),
+    }
+}
+
+/// `/eval` without a query string. A `?<params>` route only matches requests
+/// that carry a query, so this runs the same evaluation under the default budget.
+#[post("/eval/<name>", data = "<body>", rank = 2)]
+fn eval_default(name: String, body: Body<Input>, default_budget: State<Budget>) -> Negotiated {
+    let params = BudgetParam { timeout_ms: None, max_steps: None };
+    eval(name, params, body, default_budget)
+}
+
+#[get("/gen/<name>?<params>")]
+fn gen(name: String, params: CountParam) -> Negotiated {
+    if !routine::exists(&name) {
+        return Negotiated(Carrier::err(ApiError::UnknownRoutine { routine: name }));
+    }
+    Negotiated(Carrier::ok(routine::gen(&name, params.count.unwrap_or(10))))
+}
+
+/// A JSON-RPC 2.0 reply: either a negotiated body, or no content at all when
+/// every request in the call was a notification.
+struct RpcReply(Option<Value>);
+
+impl<'r> Responder<'r> for RpcReply {
+    fn respond_to(self, req: &Request) -> response::Result<'r> {
+        match self.0 {
+            None => Response::build().status(Status::NoContent).ok(),
+            Some(value) => {
+                let (content_type, body) = render(&value, wants_msgpack(req));
+                Response::build()
+                    .header(content_type)
+                    .sized_body(Cursor::new(body))
+                    .ok()
+            }
+        }
+    }
+}
+
+/// JSON-RPC 2.0 batch endpoint. The body is a single request object or an
+/// array of them; each is dispatched to one of the four routine methods and
+/// answered in order, with notifications (requests lacking `id`) producing no
+/// response.
+#[post("/rpc", data = "<body>")]
+fn rpc(body: Body<Value>, default_budget: State<Budget>) -> RpcReply {
+    match body.0 {
+        Ok(value) => RpcReply(dispatch_rpc(value, *default_budget)),
+        Err(message) => RpcReply(Some(rpc_error(Value::Null, -32700, &message))),
+    }
+}
+
+/// Route a whole JSON-RPC call (single or batch) to its response, or `None`
+/// when nothing should be written back.
+fn dispatch_rpc(body: Value, budget: Budget) -> Option<Value> {
+    match body {
+        Value::Array(items) => {
+            if items.is_empty() {
+                return Some(rpc_error(Value::Null, -32600, "invalid request"));
+            }
+            let responses: Vec<Value> = items
+                .into_iter()
+                .filter_map(|item| handle_rpc(item, budget))
+                .collect();
+            if responses.is_empty() {
+                None
+            } else {
+                Some(Value::Array(responses))
+            }
+        }
+        other => handle_rpc(other, budget),
+    }
+}
+
+/// Handle one JSON-RPC request object, returning its response object unless it
+/// is a notification.
+fn handle_rpc(request: Value, budget: Budget) -> Option<Value> {
+    let id = request.get("id").cloned();
+
+    // A notification is a *well-formed* request (it names a method) that simply
+    // omits `id`; only those are suppressed. A value that names no method is an
+    // invalid request, not a notification — e.g. a bare `1` or an object with
+    // no `method` — and must still be answered, echoing its `id` when it has
+    // one and with a null `id` otherwise.
+    let method = match request.get("method").and_then(|m| m.as_str()) {
+        Some(method) => method,
+        None => return Some(rpc_error(id.unwrap_or(Value::Null), -32600, "invalid request")),
+    };
+
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let outcome = call_method(method, params, budget);
+
+    let id = match id {
+        Some(id) => id,
+        None => return None,
+    };
+    Some(match outcome {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err((code, message)) => rpc_error(id, code, &message),
+    })
+}
+
+/// Dispatch a single method name + params onto the `routine` layer, honoring
+/// the server default `budget` which `eval` may override via `timeout_ms` /
+/// `max_steps` params, mirroring the REST endpoint's query overrides.
+fn call_method(method: &str, params: Value, budget: Budget) -> Result<Value, (i64, String)> {
+    match method {
+        "find" => {
+            let count = params.get("count").and_then(|c| c.as_u64()).unwrap_or(100) as u32;
+            Ok(json!(routine::find(count)))
+        }
+        "examples" => {
+            let name = param_routine(&params)?;
+            Ok(json!(routine::examples(&name)))
+        }
+        "eval" => {
+            let name = param_routine(&params)?;
+            let input = params
+                .get("input")
+                .cloned()
+                .ok_or_else(|| (-32602, "missing `input`".to_string()))?;
+            let timeout_ms = params.get("timeout_ms").and_then(|v| v.as_u64());
+            let max_steps = params.get("max_steps").and_then(|v| v.as_u64());
+            let budget = budget.with_overrides(timeout_ms, max_steps);
+            match routine::eval_with(&name, &Input { input }, budget) {
+                Ok(output) => Ok(json!(output)),
+                Err(e) => {
+                    let err = ApiError::from_eval(&name, e);
+                    Err((err.code(), err.message()))
+                }
+            }
+        }
+        "gen" => {
+            let name = param_routine(&params)?;
+            let count = params.get("count").and_then(|c| c.as_u64()).unwrap_or(10) as u32;
+            Ok(json!(routine::gen(&name, count)))
+        }
+        _ => Err((-32601, "method not found".to_string())),
+    }
+}
+
+/// Pull the required `routine` name out of a params object.
+fn param_routine(params: &Value) -> Result<String, (i64, String)> {
+    params
+        .get("routine")
+        .and_then(|r| r.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| (-32602, "missing `routine`".to_string()))
+}
+
+/// Build a JSON-RPC error response object.
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+}
+
+/// Mount every endpoint onto `rocket`, seeding the default evaluation budget
+/// that per-request query parameters can override, in either direction.
+///
+/// The default budget is read from the Rocket `Config` extras `eval_timeout_ms`
+/// and `eval_max_steps`, falling back to [`Budget::default`] for whichever is
+/// absent, so an operator can loosen or tighten the server-wide default without
+/// touching code.
+pub fn mount(rocket: Rocket) -> Rocket {
+    let budget = budget_from_config(rocket.config());
+    rocket
+        .manage(budget)
+        .mount("/", routes![find, examples, eval, eval_default, gen, rpc])
+}
+
+/// Derive the server-wide default [`Budget`] from Rocket's configuration.
+fn budget_from_config(config: &Config) -> Budget {
+    let timeout_ms = config.get_int("eval_timeout_ms").ok().map(|n| n as u64);
+    let max_steps = config.get_int("eval_max_steps").ok().map(|n| n as u64);
+    Budget::default().with_overrides(timeout_ms, max_steps)
+}
@@ -0,0 +1,97 @@
+//! A typed Rust client for the routines server.
+//!
+//! Other programs in this workspace used to hand-build URLs like
+//! `format!("/eval/{}", routine)` and re-implement the `{result, error}`
+//! unwrap dance themselves. [`Client`] does it once, reusing the crate's own
+//! `Input`/`Output`/`ApiError` types so there is a single source of truth for
+//! the wire format.
+
+use reqwest;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use api::ApiError;
+use routine::{Input, Output};
+
+/// The result of a client call: either the decoded value or a typed
+/// [`ApiError`].
+pub type Result<T> = ::std::result::Result<T, ApiError>;
+
+/// A handle to a routines server: a base URL plus a reusable connection pool.
+pub struct Client {
+    base: String,
+    http: reqwest::Client,
+}
+
+/// The server's reply envelope, mirrored on the client side.
+#[derive(Deserialize)]
+struct Carrier<T> {
+    result: Option<T>,
+    error: Option<ApiError>,
+}
+
+impl Client {
+    /// Build a client pointed at `base` (e.g. `"http://localhost:8000"`).
+    pub fn new<S: Into<String>>(base: S) -> Client {
+        Client {
+            base: base.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// The names of up to `count` routines the server knows about. Returns an
+    /// empty list if the server is unreachable.
+    pub fn find(&self, count: u32) -> Vec<String> {
+        self.get(&format!("/find?count={}", count)).unwrap_or_default()
+    }
+
+    /// Worked examples demonstrating a routine. Returns an empty list if the
+    /// routine is unknown or the server is unreachable.
+    pub fn examples(&self, routine: &str) -> Vec<Input> {
+        self.get(&format!("/examples/{}", routine)).unwrap_or_default()
+    }
+
+    /// Evaluate `routine` on a single input, surfacing the server's typed
+    /// error on failure.
+    pub fn eval(&self, routine: &str, input: &Input) -> Result<Output> {
+        self.post(&format!("/eval/{}", routine), input)
+    }
+
+    /// Freshly generated inputs for a routine. Returns an empty list if the
+    /// routine is unknown or the server is unreachable.
+    pub fn gen(&self, routine: &str, count: u32) -> Vec<Input> {
+        self.get(&format!("/gen/{}?count={}", routine, count))
+            .unwrap_or_default()
+    }
+
+    /// Issue a GET and unwrap the carrier.
+    fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base, path);
+        let mut resp = self.http.get(&url).send().map_err(transport)?;
+        unwrap(resp.json().map_err(transport)?)
+    }
+
+    /// Issue a POST carrying `body` as JSON and unwrap the carrier.
+    fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        let url = format!("{}{}", self.base, path);
+        let mut resp = self.http.post(&url).json(body).send().map_err(transport)?;
+        unwrap(resp.json().map_err(transport)?)
+    }
+}
+
+/// Turn a transport-level failure into an `Internal` error.
+fn transport<E: ::std::fmt::Display>(e: E) -> ApiError {
+    ApiError::Internal {
+        message: e.to_string(),
+    }
+}
+
+/// Collapse a decoded carrier into a `Result`.
+fn unwrap<T>(carrier: Carrier<T>) -> Result<T> {
+    if let Some(error) = carrier.error {
+        return Err(error);
+    }
+    carrier.result.ok_or_else(|| ApiError::Internal {
+        message: "response carried neither result nor error".to_string(),
+    })
+}
@@ -0,0 +1,186 @@
+//! The dataflow graph of list primitives that every routine is built from.
+//!
+//! A routine is, at bottom, a short pipeline of these primitives; keeping them
+//! here (rather than inline in `routine`) means the evaluator and the example
+//! generator share exactly one definition of what each operation does.
+
+use std::time::{Duration, Instant};
+
+use serde_json::{self, Value};
+
+/// The concrete payload a primitive operates over: a flat list of integers.
+pub type List = Vec<i64>;
+
+/// A running budget threaded through [`Prim::apply`].
+///
+/// Rather than spawn a worker thread and abandon it on timeout — which leaks a
+/// live, CPU-burning thread per runaway request — every primitive charges the
+/// meter as it touches the list and bails the moment a limit is hit. The
+/// wall-clock check is sampled (not consulted on every element) so the hot path
+/// stays a tight loop.
+pub struct Meter {
+    steps: u64,
+    max_steps: u64,
+    deadline: Option<Instant>,
+}
+
+/// Which budget an application overran.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overrun {
+    /// The step/recursion-depth ceiling was reached.
+    Steps,
+    /// The wall-clock deadline was reached.
+    Time,
+}
+
+impl Meter {
+    /// How often the wall-clock deadline is sampled, in steps.
+    const TIME_CHECK_INTERVAL: u64 = 1024;
+
+    /// A meter allowing `max_steps` units of work within `timeout`.
+    pub fn new(max_steps: u64, timeout: Option<Duration>) -> Meter {
+        Meter {
+            steps: 0,
+            max_steps,
+            deadline: timeout.map(|t| Instant::now() + t),
+        }
+    }
+
+    /// Charge one unit of work, reporting which budget (if any) it exhausted.
+    fn charge(&mut self) -> Result<(), Overrun> {
+        self.steps += 1;
+        if self.steps > self.max_steps {
+            return Err(Overrun::Steps);
+        }
+        if self.steps % Meter::TIME_CHECK_INTERVAL == 0 {
+            if let Some(deadline) = self.deadline {
+                if Instant::now() >= deadline {
+                    return Err(Overrun::Time);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A primitive list operation. Each variant maps a list either to another
+/// list or to a scalar; the result is rendered back into a `Value` so the API
+/// layer never has to know about the intermediate representation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prim {
+    Reverse,
+    Sort,
+    Sum,
+    Length,
+    Max,
+    Head,
+    Last,
+}
+
+impl Prim {
+    /// Every primitive the crate knows about, in a stable order.
+    pub fn all() -> &'static [Prim] {
+        &[
+            Prim::Reverse,
+            Prim::Sort,
+            Prim::Sum,
+            Prim::Length,
+            Prim::Max,
+            Prim::Head,
+            Prim::Last,
+        ]
+    }
+
+    /// The canonical routine name for this primitive.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Prim::Reverse => "reverse",
+            Prim::Sort => "sort",
+            Prim::Sum => "sum",
+            Prim::Length => "length",
+            Prim::Max => "max",
+            Prim::Head => "head",
+            Prim::Last => "last",
+        }
+    }
+
+    /// Whether the primitive is undefined on the empty list.
+    ///
+    /// The reducers (`max`/`head`/`last`) need at least one element; `examples`
+    /// and `gen` consult this so they only ever hand a routine an input it is
+    /// actually defined on.
+    pub fn requires_nonempty(&self) -> bool {
+        match *self {
+            Prim::Max | Prim::Head | Prim::Last => true,
+            Prim::Reverse | Prim::Sort | Prim::Sum | Prim::Length => false,
+        }
+    }
+
+    /// Apply the primitive to a list under a [`Meter`], producing its JSON
+    /// result.
+    ///
+    /// Returns `Ok(None)` when the operation is undefined for the given input
+    /// (e.g. `head` of an empty list); callers turn that into an evaluation
+    /// error. Returns `Err` when the work overran its step or time budget
+    /// before finishing.
+    pub fn apply(&self, xs: &List, meter: &mut Meter) -> Result<Option<Value>, Overrun> {
+        match *self {
+            Prim::Reverse => {
+                let mut ys = Vec::with_capacity(xs.len());
+                for &x in xs.iter().rev() {
+                    meter.charge()?;
+                    ys.push(x);
+                }
+                Ok(Some(json!(ys)))
+            }
+            Prim::Sort => {
+                let mut ys = Vec::with_capacity(xs.len());
+                for &x in xs {
+                    meter.charge()?;
+                    ys.push(x);
+                }
+                ys.sort();
+                Ok(Some(json!(ys)))
+            }
+            Prim::Sum => {
+                let mut acc: i64 = 0;
+                for &x in xs {
+                    meter.charge()?;
+                    acc = acc.wrapping_add(x);
+                }
+                Ok(Some(json!(acc)))
+            }
+            Prim::Length => {
+                for _ in xs {
+                    meter.charge()?;
+                }
+                Ok(Some(json!(xs.len() as i64)))
+            }
+            Prim::Max => {
+                let mut max: Option<i64> = None;
+                for &x in xs {
+                    meter.charge()?;
+                    max = Some(max.map_or(x, |m| m.max(x)));
+                }
+                Ok(max.map(|m| json!(m)))
+            }
+            Prim::Head => {
+                meter.charge()?;
+                Ok(xs.first().cloned().map(|h| json!(h)))
+            }
+            Prim::Last => {
+                meter.charge()?;
+                Ok(xs.last().cloned().map(|l| json!(l)))
+            }
+        }
+    }
+}
+
+/// Decode a JSON value into the list the primitives operate on.
+///
+/// Accepts a bare JSON array of integers; anything else is rejected so that a
+/// malformed input surfaces as a type error rather than a panic.
+pub fn as_list(value: &Value) -> Option<List> {
+    let arr = value.as_array()?;
+    arr.iter().map(|v| v.as_i64()).collect()
+}
@@ -2,13 +2,17 @@
 #![plugin(rocket_codegen)]
 
 extern crate itertools;
+extern crate reqwest;
 extern crate rocket;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
 extern crate serde_json;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
 
 pub mod api;
+pub mod client;
 pub mod graph;
 pub mod routine;
\ No newline at end of file
@@ -0,0 +1,197 @@
+//! Routines: named list functions the server can describe, demonstrate,
+//! evaluate, and generate fresh inputs for.
+//!
+//! Each routine is a thin wrapper around a single [`graph::Prim`]. The four
+//! public entry points (`find`, `examples`, `eval`, `gen`) mirror the four REST
+//! endpoints (and the JSON-RPC methods of the same names) one-to-one so the HTTP layer stays a dumb translation of these.
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+use graph::{self, Prim};
+
+/// A ceiling on the cost of a single evaluation.
+///
+/// `/gen` can hand `/eval` arbitrary inputs, so a pathological input must not
+/// be able to wedge a worker thread forever. Every evaluation runs under one
+/// of these; the defaults are generous enough that ordinary inputs never
+/// notice, and a request may override them per-call.
+#[derive(Clone, Copy, Debug)]
+pub struct Budget {
+    /// Wall-clock ceiling for a single evaluation.
+    pub timeout: Duration,
+    /// Ceiling on the number of elements/steps an evaluation may touch.
+    pub max_steps: u64,
+}
+
+impl Default for Budget {
+    fn default() -> Budget {
+        Budget {
+            timeout: Duration::from_secs(5),
+            max_steps: 1_000_000,
+        }
+    }
+}
+
+impl Budget {
+    /// Derive a budget from this one, overriding either limit when a caller
+    /// supplies it.
+    pub fn with_overrides(&self, timeout_ms: Option<u64>, max_steps: Option<u64>) -> Budget {
+        Budget {
+            timeout: timeout_ms.map(Duration::from_millis).unwrap_or(self.timeout),
+            max_steps: max_steps.unwrap_or(self.max_steps),
+        }
+    }
+}
+
+/// A single input to a routine.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Input {
+    pub input: Value,
+}
+
+/// The result of evaluating a routine on an [`Input`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Output {
+    pub output: Value,
+}
+
+/// Why an evaluation could not produce an [`Output`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvalError {
+    /// No routine is registered under the requested name.
+    UnknownRoutine(String),
+    /// The input did not match the routine's type signature (a list of ints).
+    TypeMismatch(String),
+    /// The input was well-typed but the routine is undefined on it (e.g. `max`
+    /// of an empty list).
+    Undefined(String),
+    /// Evaluation ran past its wall-clock budget.
+    Timeout(Duration),
+    /// Evaluation tried to touch more steps than its budget allows.
+    StepLimitExceeded(u64),
+}
+
+impl ::std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            EvalError::UnknownRoutine(ref name) => write!(f, "unknown routine `{}`", name),
+            EvalError::TypeMismatch(ref msg) => write!(f, "type mismatch: {}", msg),
+            EvalError::Undefined(ref msg) => write!(f, "{}", msg),
+            EvalError::Timeout(d) => write!(f, "evaluation timed out after {:?}", d),
+            EvalError::StepLimitExceeded(n) => write!(f, "evaluation exceeded {} steps", n),
+        }
+    }
+}
+
+/// The names of up to `count` routines the server knows about.
+pub fn find(count: u32) -> Vec<String> {
+    Prim::all()
+        .iter()
+        .take(count as usize)
+        .map(|p| p.name().to_string())
+        .collect()
+}
+
+/// Look up the primitive backing a routine name.
+fn lookup(name: &str) -> Option<Prim> {
+    Prim::all().iter().cloned().find(|p| p.name() == name)
+}
+
+/// Whether a routine is registered under `name`.
+pub fn exists(name: &str) -> bool {
+    lookup(name).is_some()
+}
+
+/// A handful of worked examples demonstrating the routine's behaviour.
+pub fn examples(name: &str) -> Vec<Input> {
+    let prim = match lookup(name) {
+        Some(prim) => prim,
+        None => return Vec::new(),
+    };
+    [
+        json!([]),
+        json!([1]),
+        json!([3, 1, 2]),
+        json!([5, 5, 9, 0, 2]),
+    ]
+        .iter()
+        .filter_map(|xs| graph::as_list(xs).map(|list| (xs, list)))
+        .filter(|&(_, ref list)| !(prim.requires_nonempty() && list.is_empty()))
+        .map(|(xs, _)| Input { input: xs.clone() })
+        .collect()
+}
+
+/// Evaluate `routine` on a single input under the default [`Budget`].
+pub fn eval(name: &str, input: &Input) -> Result<Output, EvalError> {
+    eval_with(name, input, Budget::default())
+}
+
+/// Evaluate `routine` on a single input under an explicit [`Budget`].
+///
+/// The primitive charges a [`graph::Meter`] as it runs, so a runaway
+/// computation is stopped in place — by step count or wall-clock — rather than
+/// hanging the caller or leaking an abandoned worker thread.
+pub fn eval_with(name: &str, input: &Input, budget: Budget) -> Result<Output, EvalError> {
+    let prim = lookup(name).ok_or_else(|| EvalError::UnknownRoutine(name.to_string()))?;
+    let list = graph::as_list(&input.input)
+        .ok_or_else(|| EvalError::TypeMismatch("expected a list of integers".to_string()))?;
+
+    let mut meter = graph::Meter::new(budget.max_steps, Some(budget.timeout));
+    match prim.apply(&list, &mut meter) {
+        Ok(Some(output)) => Ok(Output { output }),
+        Ok(None) => Err(EvalError::Undefined(format!(
+            "`{}` is undefined for this input",
+            name
+        ))),
+        Err(graph::Overrun::Steps) => Err(EvalError::StepLimitExceeded(budget.max_steps)),
+        Err(graph::Overrun::Time) => Err(EvalError::Timeout(budget.timeout)),
+    }
+}
+
+/// Generate `count` fresh, well-typed inputs for a routine.
+///
+/// The inputs are drawn deterministically from the routine name so repeated
+/// calls are reproducible — handy when a caller wants to line up generated
+/// inputs with a cached evaluation.
+pub fn gen(name: &str, count: u32) -> Vec<Input> {
+    let prim = match lookup(name) {
+        Some(prim) => prim,
+        None => return Vec::new(),
+    };
+    // Reducers are undefined on the empty list, so draw at least one element
+    // for them; everything else may legitimately see `[]`.
+    let min_len = if prim.requires_nonempty() { 1 } else { 0 };
+    let mut rng = Rng::seeded(name);
+    (0..count)
+        .map(|_| {
+            let len = min_len + (rng.next() % (6 - min_len as u64)) as usize;
+            let xs: Vec<i64> = (0..len).map(|_| (rng.next() % 20) as i64).collect();
+            Input { input: json!(xs) }
+        })
+        .collect()
+}
+
+/// A tiny deterministic xorshift generator — enough to sprinkle inputs around
+/// without pulling in the `rand` crate for a handful of small lists.
+struct Rng(u64);
+
+impl Rng {
+    fn seeded(name: &str) -> Rng {
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        for b in name.bytes() {
+            seed = seed.wrapping_mul(31).wrapping_add(u64::from(b));
+        }
+        Rng(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
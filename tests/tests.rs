@@ -3,10 +3,15 @@ extern crate rocket;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[macro_use]
 extern crate serde_json;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
 
 use rocket::local::{Client, LocalResponse};
 use rocket::http::Status;
+#[cfg(feature = "msgpack")]
+use rocket::http::Header;
 use rocket::config::{Config, Environment, LoggingLevel};
 use serde::de::DeserializeOwned;
 
@@ -88,4 +93,102 @@ fn api_success() {
             )
         }
     }
+}
+
+/// Read a response body as a raw JSON value, without the happy-path unwrapping
+/// `json_resp` does — for the error and JSON-RPC cases that carry no `result`.
+fn value_resp(mut resp: LocalResponse) -> serde_json::Value {
+    let body = resp.body().expect("get body").into_bytes().expect("read body");
+    serde_json::from_slice(&body).expect("parse body as json")
+}
+
+/// POST a JSON-RPC body to `/rpc` and decode the reply as a value.
+fn rpc_post(client: &Client, body: &str) -> serde_json::Value {
+    value_resp(client.post("/rpc").body(body).dispatch())
+}
+
+#[test]
+fn rpc_batch_suppresses_notifications_and_preserves_order() {
+    let client = connect_to_new_server();
+    let body = r#"[
+        {"jsonrpc":"2.0","method":"eval","params":{"routine":"sum","input":[1,2,3]},"id":1},
+        {"jsonrpc":"2.0","method":"eval","params":{"routine":"sum","input":[4,5]}},
+        {"jsonrpc":"2.0","method":"eval","params":{"routine":"sum","input":[6,7]},"id":"last"}
+    ]"#;
+    let resp = rpc_post(&client, body);
+    let arr = resp.as_array().expect("batch reply is an array");
+    // The middle element is a notification (no `id`) and must be dropped.
+    assert_eq!(2, arr.len());
+    assert_eq!(json!(1), arr[0]["id"]);
+    assert_eq!(json!(6), arr[0]["result"]["output"]);
+    assert_eq!(json!("last"), arr[1]["id"]);
+    assert_eq!(json!(13), arr[1]["result"]["output"]);
+}
+
+#[test]
+fn rpc_empty_batch_is_invalid_request() {
+    let client = connect_to_new_server();
+    let resp = rpc_post(&client, "[]");
+    assert_eq!(json!(-32600), resp["error"]["code"]);
+}
+
+#[test]
+fn rpc_malformed_elements_are_answered() {
+    let client = connect_to_new_server();
+    let resp = rpc_post(&client, "[1,2,3]");
+    let arr = resp.as_array().expect("batch reply is an array");
+    assert_eq!(3, arr.len());
+    for element in arr {
+        assert_eq!(json!(-32600), element["error"]["code"]);
+        assert_eq!(serde_json::Value::Null, element["id"]);
+    }
+}
+
+#[test]
+fn eval_unknown_routine_has_typed_kind() {
+    let client = connect_to_new_server();
+    let resp = value_resp(
+        client
+            .post("/eval/definitely_not_a_routine")
+            .body(r#"{"input":[1,2,3]}"#)
+            .dispatch(),
+    );
+    assert_eq!(json!("unknown_routine"), resp["error"]["kind"]);
+}
+
+#[test]
+fn eval_step_limit_is_a_skippable_error() {
+    let client = connect_to_new_server();
+    let resp = value_resp(
+        client
+            .post("/eval/sum?max_steps=0")
+            .body(r#"{"input":[1,2,3]}"#)
+            .dispatch(),
+    );
+    assert_eq!(json!("step_limit_exceeded"), resp["error"]["kind"]);
+}
+
+/// The error carrier must round-trip over MessagePack, not just JSON — the
+/// whole point of negotiating `application/msgpack` for high-volume callers.
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_error_carrier_round_trips() {
+    #[derive(Deserialize)]
+    struct ErrCarrier {
+        error: Option<MsgError>,
+    }
+    #[derive(Deserialize)]
+    struct MsgError {
+        kind: String,
+    }
+
+    let client = connect_to_new_server();
+    let mut resp = client
+        .post("/eval/definitely_not_a_routine")
+        .header(Header::new("Accept", "application/msgpack"))
+        .body(r#"{"input":[1,2,3]}"#)
+        .dispatch();
+    let body = resp.body().expect("get body").into_bytes().expect("read body");
+    let carrier: ErrCarrier = rmp_serde::from_slice(&body).expect("decode msgpack carrier");
+    assert_eq!("unknown_routine", carrier.error.expect("error present").kind);
 }
\ No newline at end of file